@@ -32,9 +32,149 @@ impl FixedBinnedVector {
         self.elements[bin] += 1;
     }
 
+    /// Wraps pre-computed per-bin counts (e.g. the row/column sums of a 2D
+    /// grid) so they can be normalized the same way as bins built via
+    /// `insert`.
+    pub fn from_counts(elements: Vec<usize>) -> Self {
+        FixedBinnedVector {
+            elements,
+            min: 0.0,
+            max: 0.0,
+            bin_size: 0.0,
+        }
+    }
+
     pub fn normalize(self) -> Vec<f32> {
         let max_count = *self.elements.iter().max().unwrap() as f32;
 
         self.elements.into_iter().map(|c| (c as f32) / max_count).collect()
     }
+}
+
+/// A 2D generalization of `FixedBinnedVector`: bins coordinate pairs into a
+/// fixed-size grid of counters, clamping out-of-range values into the edge
+/// bins along each axis.
+pub struct FixedBinnedGrid {
+    counts: Vec<u64>,
+    min_x: f32,
+    min_y: f32,
+    bin_x_size: f32,
+    bin_y_size: f32,
+    width: usize,
+    height: usize,
+}
+
+/// Ceiling on the total `counts` allocation (`width * height *
+/// size_of::<u64>()`), independent of the per-axis `max_dimension` guard.
+/// Two dimensions can each pass the per-axis check yet still multiply out
+/// to tens of gigabytes (e.g. `65536 x 65536`), so the product needs its
+/// own cap.
+const MAX_GRID_BYTES: usize = 1 << 30;
+
+impl FixedBinnedGrid {
+    /// Builds a `width` x `height` grid, guarding against the
+    /// `--xsize`/`--ysize` the user passed in before allocating the count
+    /// buffer: rejects zero dimensions, rejects either dimension exceeding
+    /// `max_dimension`, uses `checked_mul` so a pair that would overflow
+    /// `width * height` returns a clear error instead of panicking or
+    /// silently wrapping, and rejects a product that would allocate more
+    /// than `MAX_GRID_BYTES`.
+    pub fn new(
+        xrange: (f32, f32),
+        yrange: (f32, f32),
+        width: usize,
+        height: usize,
+        max_dimension: usize,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        if width == 0 || height == 0 {
+            return Err("grid width and height must be non-zero".into());
+        }
+
+        if width > max_dimension || height > max_dimension {
+            return Err(format!(
+                "grid dimension {}x{} exceeds the configured maximum of {}",
+                width, height, max_dimension
+            )
+            .into());
+        }
+
+        let cells = width
+            .checked_mul(height)
+            .ok_or("image would require a buffer too large to represent")?;
+
+        cells
+            .checked_mul(std::mem::size_of::<u64>())
+            .filter(|&bytes| bytes <= MAX_GRID_BYTES)
+            .ok_or("image would require a buffer too large to represent")?;
+
+        Ok(FixedBinnedGrid {
+            counts: vec![0; cells],
+            min_x: xrange.0,
+            min_y: yrange.0,
+            bin_x_size: (xrange.1 - xrange.0) / (width as f32),
+            bin_y_size: (yrange.1 - yrange.0) / (height as f32),
+            width,
+            height,
+        })
+    }
+
+    pub fn insert(&mut self, x: f32, y: f32) {
+        let mut x_bin = if x < self.min_x {
+            0
+        } else {
+            ((x - self.min_x) / self.bin_x_size) as usize
+        };
+        if x_bin >= self.width {
+            x_bin = self.width - 1;
+        }
+
+        let mut y_bin = if y < self.min_y {
+            0
+        } else {
+            ((y - self.min_y) / self.bin_y_size) as usize
+        };
+        if y_bin >= self.height {
+            y_bin = self.height - 1;
+        }
+
+        self.counts[y_bin * self.width + x_bin] += 1;
+    }
+
+    pub fn into_counts(self) -> Vec<u64> {
+        self.counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_zero_dimensions() {
+        assert!(FixedBinnedGrid::new((0.0, 1.0), (0.0, 1.0), 0, 10, 1 << 16).is_err());
+        assert!(FixedBinnedGrid::new((0.0, 1.0), (0.0, 1.0), 10, 0, 1 << 16).is_err());
+    }
+
+    #[test]
+    fn rejects_dimension_over_max() {
+        assert!(FixedBinnedGrid::new((0.0, 1.0), (0.0, 1.0), 100, 10, 50).is_err());
+    }
+
+    #[test]
+    fn rejects_product_overflow() {
+        assert!(FixedBinnedGrid::new((0.0, 1.0), (0.0, 1.0), usize::MAX, 2, usize::MAX).is_err());
+    }
+
+    #[test]
+    fn rejects_oversized_allocation_even_within_per_axis_max() {
+        // Each dimension alone is within max_dimension, but the product
+        // would require tens of gigabytes, which must still be rejected.
+        assert!(FixedBinnedGrid::new((0.0, 1.0), (0.0, 1.0), 1 << 16, 1 << 16, 1 << 16).is_err());
+    }
+
+    #[test]
+    fn accepts_reasonable_size() {
+        let grid = FixedBinnedGrid::new((0.0, 10.0), (0.0, 10.0), 800, 800, 1 << 16).unwrap();
+        assert_eq!(grid.into_counts().len(), 800 * 800);
+    }
 }
\ No newline at end of file
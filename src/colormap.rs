@@ -0,0 +1,128 @@
+use plotters::style::RGBColor;
+use std::sync::OnceLock;
+
+/// Color lightness for min and max values, used by the `hsl` colormap
+const MIN_L: f64 = 0.3;
+const MAX_L: f64 = 1.0;
+
+/// Anchor colors used to build the `viridis` table via linear interpolation
+const VIRIDIS_ANCHORS: &[(u8, u8, u8)] = &[
+    (68, 1, 84),
+    (59, 82, 139),
+    (33, 145, 140),
+    (94, 201, 98),
+    (253, 231, 37),
+];
+
+/// Anchor colors used to build the `magma` table via linear interpolation
+const MAGMA_ANCHORS: &[(u8, u8, u8)] = &[
+    (0, 0, 4),
+    (81, 18, 124),
+    (183, 55, 121),
+    (252, 137, 97),
+    (252, 253, 191),
+];
+
+static VIRIDIS: OnceLock<Vec<RGBColor>> = OnceLock::new();
+static MAGMA: OnceLock<Vec<RGBColor>> = OnceLock::new();
+
+/// Builds a 256-entry RGB lookup table by linearly interpolating between
+/// `anchors`.
+fn build_table(anchors: &[(u8, u8, u8)]) -> Vec<RGBColor> {
+    let segments = anchors.len() - 1;
+
+    (0..256)
+        .map(|i| {
+            let t = i as f32 / 255.0 * segments as f32;
+            let seg = (t as usize).min(segments - 1);
+            let frac = t - seg as f32;
+            let (r0, g0, b0) = anchors[seg];
+            let (r1, g1, b1) = anchors[seg + 1];
+            RGBColor(lerp(r0, r1, frac), lerp(g0, g1, frac), lerp(b0, b1, frac))
+        })
+        .collect()
+}
+
+fn lerp(a: u8, b: u8, t: f32) -> u8 {
+    (a as f32 + (b as f32 - a as f32) * t) as u8
+}
+
+/// Converts an HSL color to RGB, reproducing the HSL ramp this plotter used
+/// before selectable colormaps were added.
+fn hsl_to_rgb(h: f64, s: f64, l: f64) -> RGBColor {
+    if s == 0.0 {
+        let v = (l * 255.0).round() as u8;
+        return RGBColor(v, v, v);
+    }
+
+    let q = if l < 0.5 { l * (1.0 + s) } else { l + s - l * s };
+    let p = 2.0 * l - q;
+
+    let hue_to_rgb = |p: f64, q: f64, mut t: f64| -> f64 {
+        if t < 0.0 {
+            t += 1.0;
+        }
+        if t > 1.0 {
+            t -= 1.0;
+        }
+        if t < 1.0 / 6.0 {
+            return p + (q - p) * 6.0 * t;
+        }
+        if t < 1.0 / 2.0 {
+            return q;
+        }
+        if t < 2.0 / 3.0 {
+            return p + (q - p) * (2.0 / 3.0 - t) * 6.0;
+        }
+        p
+    };
+
+    RGBColor(
+        (hue_to_rgb(p, q, h + 1.0 / 3.0) * 255.0).round() as u8,
+        (hue_to_rgb(p, q, h) * 255.0).round() as u8,
+        (hue_to_rgb(p, q, h - 1.0 / 3.0) * 255.0).round() as u8,
+    )
+}
+
+/// Selectable colormaps for rendering a normalized `[0, 1]` density value
+#[derive(Clone, Copy, Debug)]
+pub enum Colormap {
+    Viridis,
+    Magma,
+    Hsl,
+}
+
+impl Colormap {
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "magma" => Colormap::Magma,
+            "hsl" => Colormap::Hsl,
+            _ => Colormap::Viridis,
+        }
+    }
+
+    /// Looks up a color for `value`, which is clamped to `[0, 1]`
+    pub fn color_for(self, value: f32) -> RGBColor {
+        let v = value.max(0.0).min(1.0);
+        match self {
+            Colormap::Viridis => VIRIDIS.get_or_init(|| build_table(VIRIDIS_ANCHORS))
+                [(v * 255.0) as usize],
+            Colormap::Magma => {
+                MAGMA.get_or_init(|| build_table(MAGMA_ANCHORS))[(v * 255.0) as usize]
+            }
+            Colormap::Hsl => {
+                let lightness = (v as f64) * (MAX_L - MIN_L) + MIN_L;
+                hsl_to_rgb(0.0, 1.0, lightness)
+            }
+        }
+    }
+}
+
+/// Moves each color channel of `prev` toward `new` by `a / 256`, mirroring
+/// the alpha-compositing the plotters bitmap backend does when blitting
+/// overlapping pixels, so accumulating contributions fade in smoothly
+/// instead of overplotting.
+pub fn blend(prev: RGBColor, new: RGBColor, a: u8) -> RGBColor {
+    let mix = |p: u8, n: u8| -> u8 { (p as i32 + (n as i32 - p as i32) * a as i32 / 256) as u8 };
+    RGBColor(mix(prev.0, new.0), mix(prev.1, new.1), mix(prev.2, new.2))
+}
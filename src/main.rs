@@ -16,58 +16,173 @@ extern crate clap;
 
 extern crate plotters;
 
+mod binnedvector;
+mod colormap;
+
 use std::cmp::max;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{BufRead, BufReader, Read};
 
-use clap::{App, Arg};
+use binnedvector::{FixedBinnedGrid, FixedBinnedVector};
+use clap::{App, Arg, ArgMatches};
+use colormap::{blend, Colormap};
+use plotters::coord::Shift;
 use plotters::prelude::*;
 
 /// How much to read at once, in bytes
 const BUFFER_SIZE: usize = 64 * 1024 * 1024;
 
-/// Color lightness for min and max values
-const MIN_L: f64 = 0.3;
-const MAX_L: f64 = 1.0;
+/// How much to widen a degenerate (min == max) axis range by, relative to the
+/// value's magnitude once that exceeds 1, so bin size computations never
+/// divide by zero.
+const RANGE_EPSILON: f32 = 1e-6;
+
+/// Default ceiling on `--xsize`/`--ysize`, overridable with
+/// `--max-dimension`. See `FixedBinnedGrid::new`.
+const DEFAULT_MAX_DIMENSION: usize = 1 << 16;
 
-// Parses a [0..1] coordinate pair from a line of text
-fn parse_line(line: &str, xcol: usize, ycol: usize) -> (f32, f32) {
+// Parses a coordinate pair from a line of text. Returns `None` instead of
+// panicking when the line is missing a field or a field fails to parse.
+fn parse_line(line: &str, xcol: usize, ycol: usize) -> Option<(f32, f32)> {
     let fields: Vec<&str> = line.split_whitespace().collect();
 
-    if max(xcol, ycol) > fields.len() {
-        panic!("No such field: {}", max(xcol, ycol));
+    if max(xcol, ycol) >= fields.len() {
+        return None;
+    }
+
+    let x = fields[xcol].parse::<f32>().ok()?;
+    let y = fields[ycol].parse::<f32>().ok()?;
+    Some((x, y))
+}
+
+/// Widens a degenerate `(min, max)` range by `RANGE_EPSILON` (scaled by the
+/// value's magnitude when above 1, so f32 rounding cannot swallow it) so bin
+/// size computations never divide by zero.
+fn widen_range(min: f32, max: f32) -> (f32, f32) {
+    if (max - min).abs() < f32::EPSILON {
+        let eps = RANGE_EPSILON * min.abs().max(1.0);
+        (min - eps, max + eps)
     } else {
-        (
-            fields[xcol].parse::<f32>().unwrap(),
-            fields[ycol].parse::<f32>().unwrap(),
-        )
+        (min, max)
     }
 }
 
-/// Draws a heat map with axis descriptions
+/// First pass over a whitespace-separated `source`: computes the (min, max)
+/// bounds of the x and y columns, skipping `NaN`/infinite values and lines
+/// that fail to parse rather than panicking.
+fn compute_ranges(
+    source: &str,
+    xcol: usize,
+    ycol: usize,
+) -> Result<((f32, f32), (f32, f32)), Box<dyn std::error::Error>> {
+    let reader = BufReader::with_capacity(BUFFER_SIZE, File::open(source)?);
+
+    let (mut xmin, mut xmax) = (f32::INFINITY, f32::NEG_INFINITY);
+    let (mut ymin, mut ymax) = (f32::INFINITY, f32::NEG_INFINITY);
+
+    for line in reader.lines().skip(1) {
+        if let Some((x, y)) = parse_line(&line?, xcol, ycol) {
+            if x.is_finite() && y.is_finite() {
+                xmin = xmin.min(x);
+                xmax = xmax.max(x);
+                ymin = ymin.min(y);
+                ymax = ymax.max(y);
+            }
+        }
+    }
+
+    Ok((widen_range(xmin, xmax), widen_range(ymin, ymax)))
+}
+
+/// First pass over a `--binary` `source`: computes the (min, max) bounds of
+/// the x and y fields the same way as `compute_ranges`, but decoding
+/// fixed-width records instead of parsing text lines.
+fn compute_ranges_binary(
+    source: &str,
+    layout: &BinaryLayout,
+) -> Result<((f32, f32), (f32, f32)), Box<dyn std::error::Error>> {
+    let (mut xmin, mut xmax) = (f32::INFINITY, f32::NEG_INFINITY);
+    let (mut ymin, mut ymax) = (f32::INFINITY, f32::NEG_INFINITY);
+
+    for_each_binary_record(source, layout, |x, y| {
+        if x.is_finite() && y.is_finite() {
+            xmin = xmin.min(x);
+            xmax = xmax.max(x);
+            ymin = ymin.min(y);
+            ymax = ymax.max(y);
+        }
+    })?;
+
+    Ok((widen_range(xmin, xmax), widen_range(ymin, ymax)))
+}
+
+/// Pixel width/height of the marginal histogram strips (see `marginals`)
+const MARGIN_STRIP_PX: u32 = 100;
+
+/// Draws a single heat map frame onto an existing drawing area, so
+/// `--animate` can render many frames onto a shared animated backend
+/// between `present()` calls.
 ///
 /// # Arguments
 ///
+/// * 'root' - The drawing area to render into; filled with white first
 /// * 'data' - An (unsorted) list of counters
 /// * 'size' - The size of the target image in pixels
 /// * 'xdescr' - X axis description
 /// * 'ydescr' - Y axis description
-/// * 'target' - Filename for target
-fn draw_heatmap(
+/// * 'xrange' - Data-space bounds of the X axis
+/// * 'yrange' - Data-space bounds of the Y axis
+/// * 'marginals' - Draw normalized row/column sum histograms alongside the
+///   main panel
+/// * 'max_count' - Bin count normalized to the top of the color scale; kept
+///   fixed across `--animate` frames so brightness stays comparable
+#[allow(clippy::too_many_arguments)]
+fn draw_heatmap_frame(
+    root: &DrawingArea<BitMapBackend, Shift>,
     data: Vec<u64>,
     size: (u32, u32),
     xdescr: &str,
     ydescr: &str,
-    target: &str,
+    xrange: (f32, f32),
+    yrange: (f32, f32),
+    colormap: Colormap,
+    scale: Scale,
+    marginals: bool,
+    max_count: u64,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let bitmap = BitMapBackend::new(target, size).into_drawing_area();
-    bitmap.fill(&WHITE)?;
+    root.fill(&WHITE)?;
 
-    let mut chart = ChartBuilder::on(&bitmap)
+    // Split off a top strip for the X marginal and a right strip for the Y
+    // marginal before laying out the main panel, so the main chart ends up
+    // smaller by exactly the strip size. The top strip is then itself split
+    // at the same `main_width` so its X marginal lines up with the main
+    // panel's columns instead of spanning the full (pre-right-strip) width.
+    let (top_strip, rest) = if marginals {
+        let (top, rest) = root.split_vertically(MARGIN_STRIP_PX);
+        (Some(top), rest)
+    } else {
+        (None, root.clone())
+    };
+
+    let (main_area, right_strip) = if marginals {
+        let main_width = rest.dim_in_pixel().0 - MARGIN_STRIP_PX;
+        let (main, right) = rest.split_horizontally(main_width);
+        (main, Some(right))
+    } else {
+        (rest, None)
+    };
+
+    let top_strip = top_strip.map(|top| {
+        let main_width = main_area.dim_in_pixel().0;
+        let (top_main, _top_right) = top.split_horizontally(main_width);
+        top_main
+    });
+
+    let mut chart = ChartBuilder::on(&main_area)
         .margin(10)
         .x_label_area_size(35)
         .y_label_area_size(50)
-        .build_ranged(0f32..1f32, 0f32..1f32)?;
+        .build_ranged(xrange.0..xrange.1, yrange.0..yrange.1)?;
 
     // set up mesh and axis description
     chart
@@ -76,51 +191,711 @@ fn draw_heatmap(
         .y_desc(ydescr)
         .draw()?;
 
-    let max = (*data.iter().max().unwrap() as f64).log10();
+    let max_count = max_count as f64;
+
+    // Row/column sums for the marginal histograms, computed before `data`
+    // is consumed by the pixel rasterization pass below.
+    let (col_sums, row_sums) = if marginals {
+        let mut col_sums = vec![0usize; size.0 as usize];
+        let mut row_sums = vec![0usize; size.1 as usize];
+        for (idx, &count) in data.iter().enumerate() {
+            let y = idx as u32 / size.0;
+            let x = idx as u32 - y * size.0;
+            col_sums[x as usize] += count as usize;
+            row_sums[y as usize] += count as usize;
+        }
+        (Some(col_sums), Some(row_sums))
+    } else {
+        (None, None)
+    };
 
-    // draw points of data vector
+    // Rasterize the count buffer directly: each bin maps to exactly one
+    // pixel, normalized by `scale` and colored via `colormap`, blended in
+    // from the background so sparse bins fade in rather than overplotting
+    // as the old radius-3 circles did.
     chart.draw_series(data.into_iter().enumerate().filter_map(|(idx, count)| {
-        if count > 0 {
-            let y = (idx as f32 / size.0 as f32) as u32;
-            let x = (idx as f32 - (y * size.0) as f32) as u32;
-
-            let x_pos = x as f32 / size.0 as f32;
-            let y_pos = y as f32 / size.1 as f32;
-            let lightness = ((count as f64).log10() / max as f64) * (MAX_L - MIN_L) + MIN_L;
-            let color = &HSLColor(0.0 / 360.0, 1.0, lightness);
-            Some(Circle::new((x_pos, y_pos), 3, color.filled()))
-        } else {
-            None
+        if count == 0 {
+            return None;
         }
+
+        let y = idx as u32 / size.0;
+        let x = idx as u32 - y * size.0;
+
+        let x_pos = xrange.0 + (x as f32 / size.0 as f32) * (xrange.1 - xrange.0);
+        let y_pos = yrange.0 + (y as f32 / size.1 as f32) * (yrange.1 - yrange.0);
+
+        let value = match scale {
+            // `max_count.log10()` is 0 when `max_count <= 1` (e.g. every
+            // populated bin holds exactly one sample), which would divide
+            // by zero and turn every bin into NaN. There's no dynamic range
+            // to log-scale in that case, so just light up populated bins.
+            Scale::Log if max_count > 1.0 => ((count as f64).log10() / max_count.log10()) as f32,
+            Scale::Log => 1.0,
+            Scale::Linear => (count as f64 / max_count) as f32,
+        };
+
+        let alpha = (value.max(0.0).min(1.0) * 255.0) as u8;
+        let color = blend(RGBColor(255, 255, 255), colormap.color_for(value), alpha);
+
+        Some(Pixel::new((x_pos, y_pos), color.filled()))
     }))?;
 
+    if let (Some(top), Some(col_sums)) = (top_strip, col_sums) {
+        let normalized = FixedBinnedVector::from_counts(col_sums).normalize();
+        let bin_width = (xrange.1 - xrange.0) / normalized.len() as f32;
+
+        let mut x_hist = ChartBuilder::on(&top)
+            .margin(10)
+            .y_label_area_size(50)
+            .build_ranged(xrange.0..xrange.1, 0f32..1f32)?;
+        x_hist.draw_series(normalized.iter().enumerate().map(|(i, &h)| {
+            let x0 = xrange.0 + i as f32 * bin_width;
+            Rectangle::new([(x0, 0f32), (x0 + bin_width, h)], BLUE.filled())
+        }))?;
+    }
+
+    if let (Some(right), Some(row_sums)) = (right_strip, row_sums) {
+        let normalized = FixedBinnedVector::from_counts(row_sums).normalize();
+        let bin_height = (yrange.1 - yrange.0) / normalized.len() as f32;
+
+        let mut y_hist = ChartBuilder::on(&right)
+            .margin(10)
+            .x_label_area_size(35)
+            .build_ranged(0f32..1f32, yrange.0..yrange.1)?;
+        y_hist.draw_series(normalized.iter().enumerate().map(|(i, &h)| {
+            let y0 = yrange.0 + i as f32 * bin_height;
+            Rectangle::new([(0f32, y0), (h, y0 + bin_height)], BLUE.filled())
+        }))?;
+    }
+
     Ok(())
 }
 
-/// Reads coordinate pairs from a file into an array
+/// Draws a single static heat map image to `target`
+#[allow(clippy::too_many_arguments)]
+fn draw_heatmap(
+    data: Vec<u64>,
+    size: (u32, u32),
+    xdescr: &str,
+    ydescr: &str,
+    target: &str,
+    xrange: (f32, f32),
+    yrange: (f32, f32),
+    colormap: Colormap,
+    scale: Scale,
+    marginals: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let max_count = *data.iter().max().unwrap();
+    let root = BitMapBackend::new(target, size).into_drawing_area();
+    draw_heatmap_frame(
+        &root, data, size, xdescr, ydescr, xrange, yrange, colormap, scale, marginals, max_count,
+    )
+}
+
+/// Normalization applied to bin counts before colormap lookup
+#[derive(Clone, Copy)]
+enum Scale {
+    Log,
+    Linear,
+}
+
+/// Byte order of a `--binary` input stream
+#[derive(Clone, Copy)]
+enum Endian {
+    Little,
+    Big,
+}
+
+/// Numeric width of a single field in a `--binary` input stream
+#[derive(Clone, Copy)]
+enum FloatWidth {
+    F32,
+    F64,
+}
+
+/// Describes how coordinate pairs are packed into fixed-width records for
+/// `--binary` input mode
+struct BinaryLayout {
+    endian: Endian,
+    width: FloatWidth,
+    floats_per_record: usize,
+    xcol: usize,
+    ycol: usize,
+}
+
+impl BinaryLayout {
+    /// Validates that `xcol`/`ycol` actually fall within a record before
+    /// `decode_field` ever slices into one, so a mismatched `--record-size`
+    /// produces a clear error instead of an out-of-bounds panic.
+    fn new(
+        endian: Endian,
+        width: FloatWidth,
+        floats_per_record: usize,
+        xcol: usize,
+        ycol: usize,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        if floats_per_record == 0 {
+            return Err("--record-size must be greater than zero".into());
+        }
+
+        if xcol >= floats_per_record || ycol >= floats_per_record {
+            return Err(format!(
+                "--x/--y column {}/{} out of range for --record-size {}",
+                xcol, ycol, floats_per_record
+            )
+            .into());
+        }
+
+        Ok(BinaryLayout {
+            endian,
+            width,
+            floats_per_record,
+            xcol,
+            ycol,
+        })
+    }
+
+    fn field_size(&self) -> usize {
+        match self.width {
+            FloatWidth::F32 => 4,
+            FloatWidth::F64 => 8,
+        }
+    }
+
+    fn record_size(&self) -> usize {
+        self.floats_per_record * self.field_size()
+    }
+
+    /// Decodes a single field at `field` within `record`
+    fn decode_field(&self, record: &[u8], field: usize) -> f32 {
+        let size = self.field_size();
+        let bytes = &record[field * size..(field + 1) * size];
+        match self.width {
+            FloatWidth::F32 => {
+                let raw = match self.endian {
+                    Endian::Little => u32::from_le_bytes(bytes.try_into().unwrap()),
+                    Endian::Big => u32::from_be_bytes(bytes.try_into().unwrap()),
+                };
+                f32::from_bits(raw)
+            }
+            FloatWidth::F64 => {
+                let raw = match self.endian {
+                    Endian::Little => u64::from_le_bytes(bytes.try_into().unwrap()),
+                    Endian::Big => u64::from_be_bytes(bytes.try_into().unwrap()),
+                };
+                f64::from_bits(raw) as f32
+            }
+        }
+    }
+}
+
+/// Pulls `BUFFER_SIZE`-sized chunks from `source` and invokes `visit` with
+/// each decoded `(x, y)` pair, decoding fixed-width records directly via
+/// `u32::from_be_bytes`/`u32::from_le_bytes` and `f32::from_bits` (or their
+/// `f64` equivalents), per the layout's byte order. This
+/// avoids the UTF-8 validation and float-parse overhead of `parse_line` and
+/// `BufReader::lines()`.
+fn for_each_binary_record(
+    source: &str,
+    layout: &BinaryLayout,
+    mut visit: impl FnMut(f32, f32),
+) -> Result<(), Box<dyn std::error::Error>> {
+    let record_size = layout.record_size();
+    let mut file = File::open(source)?;
+    let mut chunk = vec![0u8; BUFFER_SIZE];
+    let mut pending = Vec::new();
+
+    loop {
+        let read = file.read(&mut chunk)?;
+        if read == 0 {
+            break;
+        }
+        pending.extend_from_slice(&chunk[..read]);
+
+        let mut offset = 0;
+        while offset + record_size <= pending.len() {
+            let record = &pending[offset..offset + record_size];
+            let x = layout.decode_field(record, layout.xcol);
+            let y = layout.decode_field(record, layout.ycol);
+            visit(x, y);
+            offset += record_size;
+        }
+        pending.drain(..offset);
+    }
+
+    Ok(())
+}
+
+/// Reads coordinate pairs from a raw binary stream of fixed-width records
+/// into a binned grid, using `xrange`/`yrange` as the data-space bounds of
+/// the grid (see `compute_ranges_binary`).
+fn load_data_binary(
+    source: &str,
+    layout: &BinaryLayout,
+    size: (u32, u32),
+    xrange: (f32, f32),
+    yrange: (f32, f32),
+    max_dimension: usize,
+) -> Result<Vec<u64>, Box<dyn std::error::Error>> {
+    let mut grid = FixedBinnedGrid::new(
+        xrange,
+        yrange,
+        size.0 as usize,
+        size.1 as usize,
+        max_dimension,
+    )?;
+
+    for_each_binary_record(source, layout, |x, y| grid.insert(x, y))?;
+
+    Ok(grid.into_counts())
+}
+
+/// Reads coordinate pairs from a file into a binned grid, using
+/// `xrange`/`yrange` as the data-space bounds of the grid (see
+/// `compute_ranges`). Only rows for which `include` returns `true` are
+/// binned, which `--animate` uses to slice a file into per-frame grids
+/// without re-binning unrelated rows. Lines that fail to parse are skipped
+/// rather than panicking.
+fn load_data_filtered(
+    source: &str,
+    xcol: usize,
+    ycol: usize,
+    size: (u32, u32),
+    xrange: (f32, f32),
+    yrange: (f32, f32),
+    max_dimension: usize,
+    mut include: impl FnMut(usize, &str) -> bool,
+) -> Result<Vec<u64>, Box<dyn std::error::Error>> {
+    let mut grid = FixedBinnedGrid::new(
+        xrange,
+        yrange,
+        size.0 as usize,
+        size.1 as usize,
+        max_dimension,
+    )?;
+
+    let reader = BufReader::with_capacity(BUFFER_SIZE, File::open(source)?);
+
+    for (row, line) in reader.lines().skip(1).enumerate() {
+        let line = line?;
+        if !include(row, &line) {
+            continue;
+        }
+        if let Some((x, y)) = parse_line(&line, xcol, ycol) {
+            grid.insert(x, y);
+        }
+    }
+
+    Ok(grid.into_counts())
+}
+
+/// Reads coordinate pairs from a file into a binned grid, using
+/// `xrange`/`yrange` as the data-space bounds of the grid (see
+/// `compute_ranges`). Lines that fail to parse are skipped rather than
+/// panicking.
 fn load_data(
     source: &str,
     xcol: usize,
     ycol: usize,
     size: (u32, u32),
+    xrange: (f32, f32),
+    yrange: (f32, f32),
+    max_dimension: usize,
 ) -> Result<Vec<u64>, Box<dyn std::error::Error>> {
-    let mut v = vec![0u64; size.0 as usize * size.1 as usize];
+    load_data_filtered(
+        source,
+        xcol,
+        ycol,
+        size,
+        xrange,
+        yrange,
+        max_dimension,
+        |_, _| true,
+    )
+}
 
-    // Calculate bin sizes for x and y, based on the pixel size of the target
-    // image.
-    let bin_x_size = 1f32 / (size.0 - 1) as f32;
-    let bin_y_size = 1f32 / (size.1 - 1) as f32;
+/// Counts the data rows in `source`, i.e. the lines after the header.
+fn count_rows(source: &str) -> Result<usize, Box<dyn std::error::Error>> {
+    let reader = BufReader::with_capacity(BUFFER_SIZE, File::open(source)?);
+    Ok(reader.lines().skip(1).count())
+}
 
+/// First pass over `source`: computes the (min, max) bounds of a single
+/// column, the same way `compute_ranges` does for a coordinate pair.
+fn compute_range_1d(source: &str, col: usize) -> Result<(f32, f32), Box<dyn std::error::Error>> {
     let reader = BufReader::with_capacity(BUFFER_SIZE, File::open(source)?);
 
+    let (mut min, mut max) = (f32::INFINITY, f32::NEG_INFINITY);
+
     for line in reader.lines().skip(1) {
-        let pair = parse_line(&(line.unwrap()), xcol, ycol);
-        let x_bin = (pair.0 / bin_x_size) as usize;
-        let y_bin = (pair.1 / bin_y_size) as usize;
-        v[y_bin * size.1 as usize + x_bin] += 1;
+        if let Some((v, _)) = parse_line(&line?, col, col) {
+            if v.is_finite() {
+                min = min.min(v);
+                max = max.max(v);
+            }
+        }
+    }
+
+    Ok(widen_range(min, max))
+}
+
+/// Reads a single column from a file into a `FixedBinnedVector` and
+/// returns its normalized bar heights, without loading the whole file into
+/// memory.
+fn load_hist1d(
+    source: &str,
+    col: usize,
+    bins: usize,
+    range: (f32, f32),
+) -> Result<Vec<f32>, Box<dyn std::error::Error>> {
+    let mut binned = FixedBinnedVector::new(range.0, range.1, bins);
+
+    let reader = BufReader::with_capacity(BUFFER_SIZE, File::open(source)?);
+
+    for line in reader.lines().skip(1) {
+        if let Some((v, _)) = parse_line(&line?, col, col) {
+            binned.insert(v);
+        }
+    }
+
+    Ok(binned.normalize())
+}
+
+/// Draws a 1D histogram bar chart from pre-binned, normalized bar heights
+fn draw_histogram(
+    heights: Vec<f32>,
+    range: (f32, f32),
+    size: (u32, u32),
+    xdescr: &str,
+    target: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let bitmap = BitMapBackend::new(target, size).into_drawing_area();
+    bitmap.fill(&WHITE)?;
+
+    let mut chart = ChartBuilder::on(&bitmap)
+        .margin(10)
+        .x_label_area_size(35)
+        .y_label_area_size(50)
+        .build_ranged(range.0..range.1, 0f32..1f32)?;
+
+    chart
+        .configure_mesh()
+        .x_desc(xdescr)
+        .y_desc("Normalized count")
+        .draw()?;
+
+    let bin_width = (range.1 - range.0) / heights.len() as f32;
+
+    chart.draw_series(heights.iter().enumerate().map(|(i, &h)| {
+        let x0 = range.0 + i as f32 * bin_width;
+        let x1 = x0 + bin_width;
+        Rectangle::new([(x0, 0f32), (x1, h)], BLUE.filled())
+    }))?;
+
+    Ok(())
+}
+
+/// Runs the `hist1d` mode: bins a single column into a `FixedBinnedVector`
+/// and renders it as a bar chart.
+fn run_hist1d(matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    let source = matches.value_of("source").unwrap();
+    let col = matches
+        .value_of("x")
+        .unwrap_or("6")
+        .parse::<usize>()
+        .unwrap();
+    let bins = matches.value_of("bins").unwrap().parse::<usize>().unwrap();
+
+    let min = matches.value_of("min").map(|v| v.parse::<f32>().unwrap());
+    let max = matches.value_of("max").map(|v| v.parse::<f32>().unwrap());
+
+    let range = match (min, max) {
+        (Some(min), Some(max)) => (min, max),
+        _ => {
+            let auto_range = compute_range_1d(source, col)?;
+            (min.unwrap_or(auto_range.0), max.unwrap_or(auto_range.1))
+        }
+    };
+
+    let size = (
+        matches
+            .value_of("xsize")
+            .unwrap_or("800")
+            .parse::<u32>()
+            .unwrap(),
+        matches
+            .value_of("ysize")
+            .unwrap_or("800")
+            .parse::<u32>()
+            .unwrap(),
+    );
+
+    let heights = load_hist1d(source, col, bins, range)?;
+
+    draw_histogram(
+        heights,
+        range,
+        size,
+        matches.value_of("xdesc").unwrap_or("Z0"),
+        matches.value_of("target").unwrap(),
+    )
+}
+
+/// Runs the default `heatmap` mode: bins coordinate pairs into a 2D grid
+/// and renders it as a density heatmap.
+fn run_heatmap(matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    let size = (
+        matches
+            .value_of("xsize")
+            .unwrap_or("800")
+            .parse::<u32>()
+            .unwrap(),
+        matches
+            .value_of("ysize")
+            .unwrap_or("800")
+            .parse::<u32>()
+            .unwrap(),
+    );
+
+    let xcol = matches
+        .value_of("x")
+        .unwrap_or("6")
+        .parse::<usize>()
+        .unwrap();
+    let ycol = matches
+        .value_of("y")
+        .unwrap_or("7")
+        .parse::<usize>()
+        .unwrap();
+
+    let source = matches.value_of("source").unwrap();
+
+    let xmin = matches.value_of("xmin").map(|v| v.parse::<f32>().unwrap());
+    let xmax = matches.value_of("xmax").map(|v| v.parse::<f32>().unwrap());
+    let ymin = matches.value_of("ymin").map(|v| v.parse::<f32>().unwrap());
+    let ymax = matches.value_of("ymax").map(|v| v.parse::<f32>().unwrap());
+
+    let binary_layout = if matches.is_present("binary") {
+        let endian = if matches.value_of("endian").unwrap() == "little" {
+            Endian::Little
+        } else {
+            Endian::Big
+        };
+        let width = if matches.value_of("dtype").unwrap() == "f64" {
+            FloatWidth::F64
+        } else {
+            FloatWidth::F32
+        };
+        let floats_per_record = matches
+            .value_of("record-size")
+            .unwrap()
+            .parse::<usize>()
+            .unwrap();
+
+        Some(BinaryLayout::new(
+            endian,
+            width,
+            floats_per_record,
+            xcol,
+            ycol,
+        )?)
+    } else {
+        None
+    };
+
+    // Only run the auto-ranging pass for bounds the user didn't override.
+    let (xrange, yrange) = if xmin.is_some() && xmax.is_some() && ymin.is_some() && ymax.is_some()
+    {
+        (
+            (xmin.unwrap(), xmax.unwrap()),
+            (ymin.unwrap(), ymax.unwrap()),
+        )
+    } else {
+        let (auto_xrange, auto_yrange) = match &binary_layout {
+            Some(layout) => compute_ranges_binary(source, layout)?,
+            None => compute_ranges(source, xcol, ycol)?,
+        };
+        (
+            (xmin.unwrap_or(auto_xrange.0), xmax.unwrap_or(auto_xrange.1)),
+            (ymin.unwrap_or(auto_yrange.0), ymax.unwrap_or(auto_yrange.1)),
+        )
+    };
+
+    let max_dimension = matches
+        .value_of("max-dimension")
+        .map(|v| v.parse::<usize>().unwrap())
+        .unwrap_or(DEFAULT_MAX_DIMENSION);
+
+    let data = match &binary_layout {
+        Some(layout) => load_data_binary(source, layout, size, xrange, yrange, max_dimension)?,
+        None => load_data(source, xcol, ycol, size, xrange, yrange, max_dimension)?,
+    };
+
+    let colormap = Colormap::from_name(matches.value_of("colormap").unwrap());
+    let scale = if matches.value_of("scale").unwrap() == "linear" {
+        Scale::Linear
+    } else {
+        Scale::Log
+    };
+
+    draw_heatmap(
+        data,
+        size,
+        matches.value_of("xdesc").unwrap_or("Z0"),
+        matches.value_of("ydesc").unwrap_or("Z1"),
+        matches.value_of("target").unwrap(),
+        xrange,
+        yrange,
+        colormap,
+        scale,
+        matches.is_present("marginals"),
+    )
+}
+
+/// Runs `--animate`: slices `source` into equal-sized windows, bins each
+/// window into its own grid, then writes one heatmap frame per window into
+/// an animated GIF. The color scale is computed from the max count across
+/// all frames so brightness stays comparable frame to frame. Requires
+/// plotters' `gif` feature.
+fn run_animate(matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    if matches.is_present("binary") {
+        return Err("--animate does not yet support --binary input".into());
+    }
+
+    let source = matches.value_of("source").unwrap();
+    let size = (
+        matches
+            .value_of("xsize")
+            .unwrap_or("800")
+            .parse::<u32>()
+            .unwrap(),
+        matches
+            .value_of("ysize")
+            .unwrap_or("800")
+            .parse::<u32>()
+            .unwrap(),
+    );
+
+    let xcol = matches
+        .value_of("x")
+        .unwrap_or("6")
+        .parse::<usize>()
+        .unwrap();
+    let ycol = matches
+        .value_of("y")
+        .unwrap_or("7")
+        .parse::<usize>()
+        .unwrap();
+    let frames = matches
+        .value_of("frames")
+        .unwrap()
+        .parse::<usize>()
+        .unwrap();
+    let delay_ms = matches.value_of("delay").unwrap().parse::<u32>().unwrap();
+    let max_dimension = matches
+        .value_of("max-dimension")
+        .map(|v| v.parse::<usize>().unwrap())
+        .unwrap_or(DEFAULT_MAX_DIMENSION);
+
+    let xmin = matches.value_of("xmin").map(|v| v.parse::<f32>().unwrap());
+    let xmax = matches.value_of("xmax").map(|v| v.parse::<f32>().unwrap());
+    let ymin = matches.value_of("ymin").map(|v| v.parse::<f32>().unwrap());
+    let ymax = matches.value_of("ymax").map(|v| v.parse::<f32>().unwrap());
+
+    let (xrange, yrange) = if xmin.is_some() && xmax.is_some() && ymin.is_some() && ymax.is_some()
+    {
+        (
+            (xmin.unwrap(), xmax.unwrap()),
+            (ymin.unwrap(), ymax.unwrap()),
+        )
+    } else {
+        let (auto_xrange, auto_yrange) = compute_ranges(source, xcol, ycol)?;
+        (
+            (xmin.unwrap_or(auto_xrange.0), xmax.unwrap_or(auto_xrange.1)),
+            (ymin.unwrap_or(auto_yrange.0), ymax.unwrap_or(auto_yrange.1)),
+        )
+    };
+
+    // Slice either by a chosen column's value range, or by dividing the
+    // file's row count into equal windows.
+    let frame_grids: Vec<Vec<u64>> = if let Some(frame_col) = matches.value_of("frame-col") {
+        let frame_col = frame_col.parse::<usize>().unwrap();
+        let (col_min, col_max) = compute_range_1d(source, frame_col)?;
+        let width = (col_max - col_min) / frames as f32;
+
+        (0..frames)
+            .map(|frame_idx| {
+                let lo = col_min + frame_idx as f32 * width;
+                let is_last = frame_idx == frames - 1;
+                let hi = if is_last {
+                    col_max
+                } else {
+                    col_min + (frame_idx + 1) as f32 * width
+                };
+
+                load_data_filtered(source, xcol, ycol, size, xrange, yrange, max_dimension, |_, line| {
+                    match parse_line(line, frame_col, frame_col) {
+                        Some((v, _)) => v >= lo && (v < hi || is_last),
+                        None => false,
+                    }
+                })
+            })
+            .collect::<Result<_, _>>()?
+    } else {
+        let rows = count_rows(source)?;
+
+        (0..frames)
+            .map(|frame_idx| {
+                let row_lo = frame_idx * rows / frames;
+                let row_hi = if frame_idx == frames - 1 {
+                    rows
+                } else {
+                    (frame_idx + 1) * rows / frames
+                };
+
+                load_data_filtered(source, xcol, ycol, size, xrange, yrange, max_dimension, |row, _| {
+                    row >= row_lo && row < row_hi
+                })
+            })
+            .collect::<Result<_, _>>()?
+    };
+
+    let global_max = frame_grids
+        .iter()
+        .flat_map(|grid| grid.iter().max())
+        .max()
+        .copied()
+        .unwrap_or(1)
+        .max(1);
+
+    let colormap = Colormap::from_name(matches.value_of("colormap").unwrap());
+    let scale = if matches.value_of("scale").unwrap() == "linear" {
+        Scale::Linear
+    } else {
+        Scale::Log
+    };
+
+    let root = BitMapBackend::gif(matches.value_of("target").unwrap(), size, delay_ms)?
+        .into_drawing_area();
+
+    for grid in frame_grids {
+        draw_heatmap_frame(
+            &root,
+            grid,
+            size,
+            matches.value_of("xdesc").unwrap_or("Z0"),
+            matches.value_of("ydesc").unwrap_or("Z1"),
+            xrange,
+            yrange,
+            colormap,
+            scale,
+            false,
+            global_max,
+        )?;
+        root.present()?;
     }
 
-    Ok(v)
+    Ok(())
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -143,13 +918,22 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .required(true)
                 .help("Plot output image file name (.png)"),
         )
+        .arg(
+            Arg::with_name("mode")
+                .long("mode")
+                .takes_value(true)
+                .value_name("MODE")
+                .possible_values(&["heatmap", "hist1d"])
+                .default_value("heatmap")
+                .help("Plot mode: 2D density heatmap, or 1D histogram of a single column"),
+        )
         .arg(
             Arg::with_name("x")
                 .short("x")
                 .long("x")
                 .takes_value(true)
                 .value_name("COLUMN")
-                .help("Select column for X axis"),
+                .help("Select column for X axis (or the histogrammed column in hist1d mode)"),
         )
         .arg(
             Arg::with_name("y")
@@ -187,41 +971,205 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .value_name("PIXELS")
                 .help("Size in pixels (Y axis)"),
         )
+        .arg(
+            Arg::with_name("binary")
+                .long("binary")
+                .help("Read SOURCE as fixed-width binary records instead of whitespace-separated text"),
+        )
+        .arg(
+            Arg::with_name("endian")
+                .long("endian")
+                .takes_value(true)
+                .value_name("ENDIAN")
+                .possible_values(&["little", "big"])
+                .default_value("big")
+                .help("Byte order of binary records (with --binary)"),
+        )
+        .arg(
+            Arg::with_name("dtype")
+                .long("dtype")
+                .takes_value(true)
+                .value_name("TYPE")
+                .possible_values(&["f32", "f64"])
+                .default_value("f32")
+                .help("Field width of binary records (with --binary)"),
+        )
+        .arg(
+            Arg::with_name("record-size")
+                .long("record-size")
+                .takes_value(true)
+                .value_name("COUNT")
+                .default_value("2")
+                .help("Number of fields per binary record (with --binary)"),
+        )
+        .arg(
+            Arg::with_name("xmin")
+                .long("xmin")
+                .takes_value(true)
+                .value_name("VALUE")
+                .help("X axis lower bound (skips auto-ranging pass if xmax/ymin/ymax are also given)"),
+        )
+        .arg(
+            Arg::with_name("xmax")
+                .long("xmax")
+                .takes_value(true)
+                .value_name("VALUE")
+                .help("X axis upper bound (skips auto-ranging pass if xmin/ymin/ymax are also given)"),
+        )
+        .arg(
+            Arg::with_name("ymin")
+                .long("ymin")
+                .takes_value(true)
+                .value_name("VALUE")
+                .help("Y axis lower bound (skips auto-ranging pass if xmin/xmax/ymax are also given)"),
+        )
+        .arg(
+            Arg::with_name("ymax")
+                .long("ymax")
+                .takes_value(true)
+                .value_name("VALUE")
+                .help("Y axis upper bound (skips auto-ranging pass if xmin/xmax/ymin are also given)"),
+        )
+        .arg(
+            Arg::with_name("colormap")
+                .long("colormap")
+                .takes_value(true)
+                .value_name("NAME")
+                .possible_values(&["viridis", "magma", "hsl"])
+                .default_value("viridis")
+                .help("Colormap used to render bin density"),
+        )
+        .arg(
+            Arg::with_name("scale")
+                .long("scale")
+                .takes_value(true)
+                .value_name("SCALE")
+                .possible_values(&["log", "linear"])
+                .default_value("log")
+                .help("Normalization applied to bin counts before colormap lookup"),
+        )
+        .arg(
+            Arg::with_name("marginals")
+                .long("marginals")
+                .help("Draw normalized row/column sum histograms alongside the main heatmap"),
+        )
+        .arg(
+            Arg::with_name("bins")
+                .long("bins")
+                .takes_value(true)
+                .value_name("COUNT")
+                .default_value("50")
+                .help("Number of bins (hist1d mode)"),
+        )
+        .arg(
+            Arg::with_name("min")
+                .long("min")
+                .takes_value(true)
+                .value_name("VALUE")
+                .help("Value domain lower bound (hist1d mode, skips auto-ranging pass if max is also given)"),
+        )
+        .arg(
+            Arg::with_name("max")
+                .long("max")
+                .takes_value(true)
+                .value_name("VALUE")
+                .help("Value domain upper bound (hist1d mode, skips auto-ranging pass if min is also given)"),
+        )
+        .arg(
+            Arg::with_name("animate")
+                .long("animate")
+                .help("Slice SOURCE into --frames windows and write an animated GIF instead of a single image"),
+        )
+        .arg(
+            Arg::with_name("frame-col")
+                .long("frame-col")
+                .takes_value(true)
+                .value_name("COLUMN")
+                .help("Column (e.g. a timestamp) whose value range is sliced into frames (with --animate); defaults to slicing by row count"),
+        )
+        .arg(
+            Arg::with_name("frames")
+                .long("frames")
+                .takes_value(true)
+                .value_name("COUNT")
+                .default_value("10")
+                .help("Number of animation frames (with --animate)"),
+        )
+        .arg(
+            Arg::with_name("delay")
+                .long("delay")
+                .takes_value(true)
+                .value_name("MILLISECONDS")
+                .default_value("100")
+                .help("Per-frame delay in the output GIF (with --animate)"),
+        )
+        .arg(
+            Arg::with_name("max-dimension")
+                .long("max-dimension")
+                .takes_value(true)
+                .value_name("PIXELS")
+                .help("Reject --xsize/--ysize pairs larger than this before allocating the grid (default 65536)"),
+        )
         .get_matches();
 
-    let size = (
-        matches
-            .value_of("xsize")
-            .unwrap_or("800")
-            .parse::<u32>()
-            .unwrap(),
-        matches
-            .value_of("ysize")
-            .unwrap_or("800")
-            .parse::<u32>()
-            .unwrap(),
-    );
+    if matches.is_present("animate") {
+        run_animate(&matches)
+    } else if matches.value_of("mode").unwrap() == "hist1d" {
+        run_hist1d(&matches)
+    } else {
+        run_heatmap(&matches)
+    }
+}
 
-    let data = load_data(
-        matches.value_of("source").unwrap(),
-        matches
-            .value_of("x")
-            .unwrap_or("6")
-            .parse::<usize>()
-            .unwrap(),
-        matches
-            .value_of("y")
-            .unwrap_or("7")
-            .parse::<usize>()
-            .unwrap(),
-        size,
-    )?;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    draw_heatmap(
-        data,
-        size,
-        matches.value_of("xdesc").unwrap_or("Z0"),
-        matches.value_of("ydesc").unwrap_or("Z1"),
-        matches.value_of("target").unwrap(),
-    )
+    #[test]
+    fn binary_layout_rejects_column_out_of_range() {
+        assert!(BinaryLayout::new(Endian::Big, FloatWidth::F32, 2, 0, 2).is_err());
+        assert!(BinaryLayout::new(Endian::Big, FloatWidth::F32, 2, 2, 0).is_err());
+    }
+
+    #[test]
+    fn binary_layout_rejects_zero_record_size() {
+        assert!(BinaryLayout::new(Endian::Big, FloatWidth::F32, 0, 0, 0).is_err());
+    }
+
+    #[test]
+    fn decode_field_f32_big_endian() {
+        let layout = BinaryLayout::new(Endian::Big, FloatWidth::F32, 2, 0, 1).unwrap();
+        let record = 1.5f32.to_be_bytes();
+        assert_eq!(layout.decode_field(&record, 0), 1.5);
+    }
+
+    #[test]
+    fn decode_field_f32_little_endian() {
+        let layout = BinaryLayout::new(Endian::Little, FloatWidth::F32, 2, 0, 1).unwrap();
+        let record = 1.5f32.to_le_bytes();
+        assert_eq!(layout.decode_field(&record, 0), 1.5);
+    }
+
+    #[test]
+    fn decode_field_f64_big_endian() {
+        let layout = BinaryLayout::new(Endian::Big, FloatWidth::F64, 2, 0, 1).unwrap();
+        let record = 1.5f64.to_be_bytes();
+        assert_eq!(layout.decode_field(&record, 0), 1.5);
+    }
+
+    #[test]
+    fn decode_field_f64_little_endian() {
+        let layout = BinaryLayout::new(Endian::Little, FloatWidth::F64, 2, 0, 1).unwrap();
+        let record = 1.5f64.to_le_bytes();
+        assert_eq!(layout.decode_field(&record, 0), 1.5);
+    }
+
+    #[test]
+    fn decode_field_reads_second_field_at_correct_offset() {
+        let layout = BinaryLayout::new(Endian::Big, FloatWidth::F32, 2, 0, 1).unwrap();
+        let mut record = Vec::new();
+        record.extend_from_slice(&1.0f32.to_be_bytes());
+        record.extend_from_slice(&2.0f32.to_be_bytes());
+        assert_eq!(layout.decode_field(&record, 1), 2.0);
+    }
 }